@@ -1,16 +1,21 @@
 use clap::Parser;
 use core::fmt;
+use directories::ProjectDirs;
 use rayon::iter::IntoParallelRefIterator;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::{HashMap, HashSet, VecDeque},
     path::{Path, PathBuf},
-    process::Child,
+    process::{Child, ExitStatus},
 };
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone)]
 enum CommandType {
     Command(String),
+    /// Resolves to the `CommandType` registered under this name in the
+    /// config's top-level `aliases` map.
+    Alias(String),
     Execution {
         command: String,
         working_directory: Option<String>,
@@ -18,6 +23,19 @@ enum CommandType {
         args: Vec<String>,
         #[serde(default = "bool::default")]
         spawn_only: bool,
+        #[serde(default = "bool::default")]
+        continue_on_error: bool,
+        /// Environment variables to set (or override) for this command.
+        #[serde(default = "HashMap::new")]
+        env: HashMap<String, String>,
+        /// Start from an empty environment instead of inheriting the
+        /// parent's, before applying `env`.
+        #[serde(default = "bool::default")]
+        env_clear: bool,
+        /// Environment variable names to remove from the inherited
+        /// environment before applying `env`.
+        #[serde(default = "Vec::new")]
+        env_remove: Vec<String>,
     },
 }
 
@@ -25,11 +43,16 @@ impl fmt::Display for CommandType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             CommandType::Command(cmd) => write!(f, "{}", cmd),
+            CommandType::Alias(name) => write!(f, "alias {}", name),
             CommandType::Execution {
                 command,
                 working_directory,
                 args,
                 spawn_only: _,
+                continue_on_error: _,
+                env: _,
+                env_clear: _,
+                env_remove: _,
             } => write!(f, "{} in {:?} with {:?}", command, working_directory, args),
         }
     }
@@ -39,17 +62,487 @@ impl fmt::Display for CommandType {
 enum Command {
     Single(CommandType),
     Parallel(Vec<CommandType>),
+    ForEach {
+        inputs: Vec<String>,
+        template: CommandType,
+    },
+    /// Streams each stage's stdout into the next stage's stdin, like a
+    /// shell pipe.
+    Pipeline(Vec<CommandType>),
+}
+
+/// A named task in the dependency-graph scheduling mode: it only runs
+/// once every task listed in `depends_on` has completed successfully.
+#[derive(Deserialize, Serialize, Clone)]
+struct Task {
+    id: String,
+    #[serde(default = "Vec::new")]
+    depends_on: Vec<String>,
+    command: CommandType,
+}
+
+/// Top-level config: a reusable `aliases` map plus either a plain
+/// sequential/parallel `commands` list or the named-task `tasks` DAG
+/// mode (mutually exclusive; `tasks` takes precedence when both are
+/// given).
+#[derive(Serialize, Default)]
+struct Config {
+    #[serde(default)]
+    aliases: HashMap<String, CommandType>,
+    #[serde(default)]
+    commands: Vec<Command>,
+    #[serde(default)]
+    tasks: Vec<Task>,
+}
+
+/// Accepts both the current map schema and the baseline schema, which
+/// was a bare sequence of commands with no `aliases`/`tasks` support, so
+/// old config files keep parsing instead of failing with an opaque
+/// serde error.
+impl<'de> Deserialize<'de> for Config {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize, Default)]
+        struct Fields {
+            #[serde(default)]
+            aliases: HashMap<String, CommandType>,
+            #[serde(default)]
+            commands: Vec<Command>,
+            #[serde(default)]
+            tasks: Vec<Task>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Legacy(Vec<Command>),
+            Full(Fields),
+        }
+
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Legacy(commands) => Config {
+                commands,
+                ..Default::default()
+            },
+            Repr::Full(fields) => Config {
+                aliases: fields.aliases,
+                commands: fields.commands,
+                tasks: fields.tasks,
+            },
+        })
+    }
+}
+
+/// Resolves a `CommandType::Alias` to the `CommandType` registered under
+/// that name, following alias-of-alias chains, erroring if the name is
+/// undefined.
+fn resolve_alias(
+    cmd: &CommandType,
+    aliases: &HashMap<String, CommandType>,
+) -> Result<CommandType, CommandError> {
+    resolve_alias_inner(cmd, aliases, &mut HashSet::new())
+}
+
+fn resolve_alias_inner(
+    cmd: &CommandType,
+    aliases: &HashMap<String, CommandType>,
+    visited: &mut HashSet<String>,
+) -> Result<CommandType, CommandError> {
+    match cmd {
+        CommandType::Alias(name) => {
+            if !visited.insert(name.clone()) {
+                return Err(CommandError::AliasCycle(name.clone()));
+            }
+            let target = aliases
+                .get(name)
+                .ok_or_else(|| CommandError::UndefinedAlias(name.clone()))?;
+            resolve_alias_inner(target, aliases, visited)
+        }
+        other => Ok(other.clone()),
+    }
+}
+
+/// Searches the platform config directory (`$XDG_CONFIG_HOME/startr` on
+/// Linux, `Application Support` on macOS, `%APPDATA%` on Windows) for a
+/// `startr.yaml`, returning it if present.
+fn discover_config() -> Option<PathBuf> {
+    let dirs = ProjectDirs::from("", "", "startr")?;
+    let candidate = dirs.config_dir().join("startr.yaml");
+    candidate.exists().then_some(candidate)
+}
+
+/// Computes a topological order over `tasks` via Kahn's algorithm purely
+/// to detect dependency cycles up front; the real scheduler in
+/// [`run_tasks`] runs a fresh frontier each round instead of this fixed
+/// order, so that unrelated branches can run concurrently.
+fn detect_cycle(tasks: &[Task]) -> Result<(), CommandError> {
+    let ids: HashSet<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+    for task in tasks {
+        for dep in &task.depends_on {
+            if !ids.contains(dep.as_str()) {
+                return Err(CommandError::UnknownDependency {
+                    task: task.id.clone(),
+                    dependency: dep.clone(),
+                });
+            }
+        }
+    }
+
+    let mut in_degree: HashMap<&str, usize> = tasks.iter().map(|t| (t.id.as_str(), 0)).collect();
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for task in tasks {
+        for dep in &task.depends_on {
+            *in_degree.entry(task.id.as_str()).or_insert(0) += 1;
+            dependents
+                .entry(dep.as_str())
+                .or_default()
+                .push(task.id.as_str());
+        }
+    }
+
+    let mut queue: VecDeque<&str> = in_degree
+        .iter()
+        .filter(|(_, degree)| **degree == 0)
+        .map(|(id, _)| *id)
+        .collect();
+    let mut visited = 0;
+    while let Some(id) = queue.pop_front() {
+        visited += 1;
+        if let Some(deps) = dependents.get(id) {
+            for &dependent in deps {
+                let degree = in_degree.get_mut(dependent).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    queue.push_back(dependent);
+                }
+            }
+        }
+    }
+
+    if visited == tasks.len() {
+        Ok(())
+    } else {
+        let cycle = in_degree
+            .into_iter()
+            .filter(|(_, degree)| *degree > 0)
+            .map(|(id, _)| id.to_string())
+            .collect();
+        Err(CommandError::Cycle(cycle))
+    }
+}
+
+/// Runs a graph of named tasks, repeatedly launching the frontier of
+/// tasks whose dependencies have all completed successfully (via rayon)
+/// until every task has run or is blocked. A task whose dependency
+/// failed is reported as skipped rather than run.
+fn run_tasks(tasks: Vec<Task>, aliases: &HashMap<String, CommandType>) -> Result<(), CommandError> {
+    detect_cycle(&tasks)?;
+
+    let mut remaining: HashMap<String, Task> =
+        tasks.into_iter().map(|t| (t.id.clone(), t)).collect();
+    let mut succeeded: HashSet<String> = HashSet::new();
+    let mut errors: Vec<CommandError> = Vec::new();
+
+    while !remaining.is_empty() {
+        let frontier: Vec<String> = remaining
+            .values()
+            .filter(|task| task.depends_on.iter().all(|dep| succeeded.contains(dep)))
+            .map(|task| task.id.clone())
+            .collect();
+
+        if frontier.is_empty() {
+            for (id, task) in remaining {
+                let failed_dependency = task
+                    .depends_on
+                    .iter()
+                    .find(|dep| !succeeded.contains(*dep))
+                    .cloned()
+                    .unwrap_or_default();
+                errors.push(CommandError::Skipped {
+                    id,
+                    dependency: failed_dependency,
+                });
+            }
+            break;
+        }
+
+        let results: Vec<(String, Result<(), CommandError>)> = frontier
+            .par_iter()
+            .map(|id| (id.clone(), run_and_check(&remaining[id].command, aliases)))
+            .collect();
+
+        for (id, result) in results {
+            remaining.remove(&id);
+            match result {
+                Ok(()) => {
+                    succeeded.insert(id);
+                }
+                Err(error) => errors.push(error),
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(CommandError::Aggregate(errors))
+    }
+}
+
+/// fd-style `--exec` placeholder tokens, checked longest-match first so
+/// that e.g. `{/.}` isn't mistaken for `{/}` followed by stray text.
+const FOR_EACH_TOKENS: [&str; 5] = ["{/.}", "{//}", "{/}", "{.}", "{}"];
+
+fn for_each_contains_token(s: &str) -> bool {
+    FOR_EACH_TOKENS.iter().any(|token| s.contains(token))
+}
+
+/// Expands fd's `--exec` placeholder tokens in `s` for a single `input`:
+/// `{}` the full input, `{.}` without its extension, `{/}` the basename,
+/// `{//}` the parent directory, `{/.}` the basename without extension.
+fn for_each_substitute(s: &str, input: &str) -> String {
+    let path = Path::new(input);
+    let without_ext = |p: &Path| -> String {
+        match p.extension() {
+            Some(ext) => {
+                let suffix = format!(".{}", ext.to_string_lossy());
+                p.to_string_lossy()
+                    .strip_suffix(&suffix)
+                    .unwrap_or(&p.to_string_lossy())
+                    .to_string()
+            }
+            None => p.to_string_lossy().to_string(),
+        }
+    };
+    let basename = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| input.to_string());
+    // Matches fd's `--exec` semantics: an input with no directory
+    // component (e.g. `bare.txt`) yields `.`, not an empty string.
+    let parent = path
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| ".".to_string());
+
+    s.replace("{/.}", &without_ext(Path::new(&basename)))
+        .replace("{//}", &parent)
+        .replace("{/}", &basename)
+        .replace("{.}", &without_ext(path))
+        .replace("{}", input)
+}
+
+/// Builds one concrete [`CommandType`] from `template` for a single
+/// `input`, substituting placeholder tokens into every templated string.
+/// If the template contains no token at all, the input is appended as an
+/// extra argument, mirroring fd's `--exec` behavior.
+fn for_each_expand(template: &CommandType, input: &str) -> CommandType {
+    match template {
+        CommandType::Alias(_) => {
+            unreachable!("aliases are resolved via resolve_alias before templating")
+        }
+        CommandType::Command(cmd) => {
+            let expanded = for_each_substitute(cmd, input);
+            if for_each_contains_token(cmd) {
+                CommandType::Command(expanded)
+            } else {
+                CommandType::Command(format!("{} {}", expanded, input))
+            }
+        }
+        CommandType::Execution {
+            command,
+            working_directory,
+            args,
+            spawn_only,
+            continue_on_error,
+            env,
+            env_clear,
+            env_remove,
+        } => {
+            let has_token = for_each_contains_token(command)
+                || args.iter().any(|a| for_each_contains_token(a))
+                || working_directory
+                    .as_deref()
+                    .is_some_and(for_each_contains_token)
+                || env.values().any(|v| for_each_contains_token(v));
+
+            let mut expanded_args: Vec<String> =
+                args.iter().map(|a| for_each_substitute(a, input)).collect();
+            if !has_token {
+                expanded_args.push(input.to_string());
+            }
+
+            CommandType::Execution {
+                command: for_each_substitute(command, input),
+                working_directory: working_directory
+                    .as_ref()
+                    .map(|d| for_each_substitute(d, input)),
+                args: expanded_args,
+                spawn_only: *spawn_only,
+                continue_on_error: *continue_on_error,
+                env: env
+                    .iter()
+                    .map(|(k, v)| (k.clone(), for_each_substitute(v, input)))
+                    .collect(),
+                env_clear: *env_clear,
+                env_remove: env_remove.clone(),
+            }
+        }
+    }
 }
 
 #[derive(Parser, Debug)]
 struct Cli {
     #[clap(parse(from_os_str))]
     config: Option<PathBuf>,
+
+    /// Keep running subsequent commands even after one exits with a
+    /// non-zero status, instead of aborting the whole run. In `tasks` mode
+    /// every task not blocked by a failed dependency runs either way; the
+    /// flag only decides whether the failures end the program with an error.
+    #[clap(long)]
+    keep_going: bool,
+}
+
+/// An error produced while executing a configured command.
+#[derive(Debug)]
+enum CommandError {
+    /// A single command exited with a non-zero status.
+    Failed {
+        command: String,
+        working_directory: PathBuf,
+        args: Vec<String>,
+        status: ExitStatus,
+    },
+    /// One or more commands in a `Parallel` or `ForEach` batch, or tasks
+    /// in a `tasks` graph, failed or were skipped.
+    Aggregate(Vec<CommandError>),
+    /// The task dependency graph contains a cycle among these ids.
+    Cycle(Vec<String>),
+    /// A task's `depends_on` names an id that isn't defined.
+    UnknownDependency { task: String, dependency: String },
+    /// A task was not run because `dependency` did not complete
+    /// successfully.
+    Skipped { id: String, dependency: String },
+    /// A `CommandType::Alias` referenced a name missing from `aliases`.
+    UndefinedAlias(String),
+    /// Resolving an alias revisited a name already on the resolution
+    /// path, i.e. the `aliases` map contains a cycle.
+    AliasCycle(String),
+    /// The command could not even be spawned (e.g. binary not found).
+    SpawnFailed {
+        command: String,
+        working_directory: PathBuf,
+        args: Vec<String>,
+        source: std::io::Error,
+    },
+    /// A stage in a `Pipeline` exited with a non-zero status.
+    Pipeline {
+        stage: usize,
+        command: String,
+        source: Box<CommandError>,
+    },
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Failed {
+                command,
+                working_directory,
+                args,
+                status,
+            } => write!(
+                f,
+                "command `{}` in {:?} with args {:?} failed with {}",
+                command, working_directory, args, status
+            ),
+            CommandError::Aggregate(errors) => {
+                writeln!(f, "{} command(s) failed:", errors.len())?;
+                for error in errors {
+                    writeln!(f, "  - {}", error)?;
+                }
+                Ok(())
+            }
+            CommandError::Cycle(ids) => {
+                write!(
+                    f,
+                    "tasks involved in a dependency cycle: {}",
+                    ids.join(", ")
+                )
+            }
+            CommandError::UnknownDependency { task, dependency } => write!(
+                f,
+                "task `{}` depends on undefined task `{}`",
+                task, dependency
+            ),
+            CommandError::Skipped { id, dependency } => write!(
+                f,
+                "task `{}` skipped because its dependency `{}` did not succeed",
+                id, dependency
+            ),
+            CommandError::UndefinedAlias(name) => {
+                write!(f, "undefined alias `{}`", name)
+            }
+            CommandError::AliasCycle(name) => {
+                write!(f, "alias `{}` is part of a cycle of aliases", name)
+            }
+            CommandError::SpawnFailed {
+                command,
+                working_directory,
+                args,
+                source,
+            } => write!(
+                f,
+                "failed to spawn `{}` in {:?} with args {:?}: {}",
+                command, working_directory, args, source
+            ),
+            CommandError::Pipeline {
+                stage,
+                command,
+                source,
+            } => write!(
+                f,
+                "pipeline stage {} (`{}`) failed: {}",
+                stage, command, source
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// Checks the exit status of a finished command, turning a non-zero
+/// status into a [`CommandError::Failed`] that names the command, its
+/// resolved working directory, its args, and the exit code.
+fn check_exit_status(
+    command: &str,
+    working_directory: &Path,
+    args: &[String],
+    status: ExitStatus,
+) -> Result<(), CommandError> {
+    if status.success() {
+        Ok(())
+    } else {
+        Err(CommandError::Failed {
+            command: command.to_string(),
+            working_directory: working_directory.to_path_buf(),
+            args: args.to_vec(),
+            status,
+        })
+    }
 }
 
 struct ExecutionResult {
     child: std::result::Result<Child, std::io::Error>,
     wait: bool,
+    command: String,
+    working_directory: PathBuf,
+    args: Vec<String>,
+    continue_on_error: bool,
 }
 
 fn shell_command() -> std::process::Command {
@@ -64,83 +557,338 @@ fn shell_command() -> std::process::Command {
     }
 }
 
-fn run(command: &CommandType) -> ExecutionResult {
+/// Builds the `std::process::Command` for a (non-alias) `CommandType`,
+/// along with the command name, resolved working directory, and args
+/// used to report failures. Shared by [`run`] and [`run_pipeline`] so
+/// working-directory resolution and env wiring stay in one place.
+fn build_process(command: &CommandType) -> (std::process::Command, String, PathBuf, Vec<String>) {
     match command {
+        CommandType::Alias(_) => {
+            unreachable!("aliases are resolved via resolve_alias before dispatch")
+        }
         CommandType::Command(cmd) => {
-            return ExecutionResult {
-                child: shell_command().arg(cmd).spawn(),
-                wait: true,
-            };
+            let mut builder = shell_command();
+            builder.arg(cmd);
+            (
+                builder,
+                cmd.clone(),
+                std::env::current_dir().unwrap(),
+                Vec::new(),
+            )
         }
         CommandType::Execution {
             command,
             working_directory,
             args,
-            spawn_only,
+            env,
+            env_clear,
+            env_remove,
+            ..
         } => {
-            let child = std::process::Command::new(command)
-                .current_dir(
-                    working_directory
-                        .as_ref()
-                        .map_or(std::env::current_dir().unwrap(), |d| {
-                            Path::new(d).to_path_buf()
-                        }),
-                )
-                .args(args)
-                .spawn();
-            ExecutionResult {
-                child,
-                wait: !spawn_only,
+            let resolved_working_directory = working_directory
+                .as_ref()
+                .map_or(std::env::current_dir().unwrap(), |d| {
+                    Path::new(d).to_path_buf()
+                });
+            let mut builder = std::process::Command::new(command);
+            builder.current_dir(&resolved_working_directory).args(args);
+
+            if *env_clear {
+                builder.env_clear();
             }
+            for key in env_remove {
+                builder.env_remove(key);
+            }
+            builder.envs(env);
+
+            (
+                builder,
+                command.clone(),
+                resolved_working_directory,
+                args.clone(),
+            )
         }
     }
 }
 
+fn run(command: &CommandType) -> ExecutionResult {
+    let (spawn_only, continue_on_error) = match command {
+        CommandType::Execution {
+            spawn_only,
+            continue_on_error,
+            ..
+        } => (*spawn_only, *continue_on_error),
+        _ => (false, false),
+    };
+
+    let (mut builder, command, working_directory, args) = build_process(command);
+    ExecutionResult {
+        child: builder.spawn(),
+        wait: !spawn_only,
+        command,
+        working_directory,
+        args,
+        continue_on_error,
+    }
+}
+
+/// Runs a `Pipeline` of stages, streaming each stage's stdout into the
+/// next stage's stdin over a helper thread (so a large intermediate
+/// buffer can't deadlock the two processes writing/reading in lockstep),
+/// and reports the final stage's captured output. A non-zero exit at any
+/// stage is reported as a [`CommandError::Pipeline`] naming that stage.
+fn run_pipeline(
+    stages: &[CommandType],
+    aliases: &HashMap<String, CommandType>,
+) -> Result<(), CommandError> {
+    let resolved: Vec<CommandType> = stages
+        .iter()
+        .map(|stage| resolve_alias(stage, aliases))
+        .collect::<Result<_, _>>()?;
+
+    if resolved.is_empty() {
+        return Ok(());
+    }
+
+    struct RunningStage {
+        child: Child,
+        command: String,
+        working_directory: PathBuf,
+        args: Vec<String>,
+    }
+
+    let mut running = Vec::with_capacity(resolved.len());
+    let mut pumps: Vec<std::thread::JoinHandle<()>> = Vec::new();
+    let mut previous_stdout: Option<std::process::ChildStdout> = None;
+    let mut final_output: Option<std::thread::JoinHandle<Vec<u8>>> = None;
+    let last = resolved.len() - 1;
+
+    for (index, stage) in resolved.iter().enumerate() {
+        let (mut builder, command, working_directory, args) = build_process(stage);
+        builder.stdout(std::process::Stdio::piped());
+        builder.stdin(if index == 0 {
+            std::process::Stdio::inherit()
+        } else {
+            std::process::Stdio::piped()
+        });
+
+        let mut child = match builder.spawn() {
+            Ok(child) => child,
+            Err(source) => {
+                for pump in pumps {
+                    let _ = pump.join();
+                }
+                return Err(CommandError::SpawnFailed {
+                    command,
+                    working_directory,
+                    args,
+                    source,
+                });
+            }
+        };
+
+        if let Some(mut prev_stdout) = previous_stdout.take() {
+            let mut stdin = child.stdin.take().expect("pipeline stage stdin was piped");
+            pumps.push(std::thread::spawn(move || {
+                let _ = std::io::copy(&mut prev_stdout, &mut stdin);
+            }));
+        }
+
+        let stdout = child.stdout.take();
+        if index == last {
+            // Drain the final stage's stdout on its own thread as soon as
+            // it's spawned, concurrently with every earlier stage. If we
+            // instead waited to read it only after `wait()`-ing earlier
+            // stages, a large enough final output would fill its pipe
+            // buffer and block, which stalls its stdin reads, which stalls
+            // the upstream pump thread, which stalls the upstream process
+            // `wait()` is stuck on — a deadlock.
+            final_output = stdout.map(|mut stdout| {
+                std::thread::spawn(move || {
+                    let mut buffer = Vec::new();
+                    let _ = std::io::Read::read_to_end(&mut stdout, &mut buffer);
+                    buffer
+                })
+            });
+        } else {
+            previous_stdout = stdout;
+        }
+
+        running.push(RunningStage {
+            child,
+            command,
+            working_directory,
+            args,
+        });
+    }
+
+    for (index, mut stage) in running.into_iter().enumerate() {
+        let status = stage
+            .child
+            .wait()
+            .expect("failed to wait on pipeline stage");
+
+        if let Err(source) = check_exit_status(
+            &stage.command,
+            &stage.working_directory,
+            &stage.args,
+            status,
+        ) {
+            for pump in pumps {
+                let _ = pump.join();
+            }
+            if let Some(reader) = final_output {
+                let _ = reader.join();
+            }
+            return Err(CommandError::Pipeline {
+                stage: index,
+                command: stage.command,
+                source: Box::new(source),
+            });
+        }
+    }
+
+    for pump in pumps {
+        let _ = pump.join();
+    }
+    if let Some(reader) = final_output {
+        let output = reader.join().unwrap_or_default();
+        println!("{}", String::from_utf8_lossy(&output));
+    }
+
+    Ok(())
+}
+
+/// Runs a single command to completion and checks its exit status,
+/// unless it was spawned with `spawn_only`, in which case it is left
+/// running in the background and treated as successful. Resolves
+/// `CommandType::Alias` against `aliases` before dispatch.
+fn run_and_check(
+    cmd: &CommandType,
+    aliases: &HashMap<String, CommandType>,
+) -> Result<(), CommandError> {
+    let cmd = resolve_alias(cmd, aliases)?;
+    let cmd = &cmd;
+    println!("{}", cmd);
+    let result = run(cmd);
+    let child = result.child.map_err(|source| CommandError::SpawnFailed {
+        command: result.command.clone(),
+        working_directory: result.working_directory.clone(),
+        args: result.args.clone(),
+        source,
+    })?;
+
+    if !result.wait {
+        println!("spawned {}", cmd);
+        return Ok(());
+    }
+
+    let output = child.wait_with_output().expect("failed to wait on command");
+    println!("{}", String::from_utf8_lossy(&output.stdout));
+
+    let check = check_exit_status(
+        &result.command,
+        &result.working_directory,
+        &result.args,
+        output.status,
+    );
+    if check.is_err() && result.continue_on_error {
+        return Ok(());
+    }
+    check
+}
+
+/// Runs a batch of commands concurrently via rayon and aggregates every
+/// failure into a single [`CommandError::Aggregate`] instead of
+/// unwrapping, so one failing command doesn't hide the others.
+fn run_batch(
+    cmds: &[CommandType],
+    aliases: &HashMap<String, CommandType>,
+) -> Result<(), CommandError> {
+    let errors: Vec<CommandError> = cmds
+        .par_iter()
+        .filter_map(|cmd| run_and_check(cmd, aliases).err())
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(CommandError::Aggregate(errors))
+    }
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Cli::parse();
+    let cli = Cli::parse();
 
-    let config_file = match args.config {
-        Some(path) => path,
-        None => std::env::current_exe()?.with_extension("yaml"),
+    let config_file = match &cli.config {
+        Some(path) => path.clone(),
+        None => match discover_config() {
+            Some(path) => path,
+            None => std::env::current_exe()?.with_extension("yaml"),
+        },
     };
 
     println!("{}", config_file.to_string_lossy());
 
-    let config = std::fs::read_to_string(config_file)?;
+    let config_contents = std::fs::read_to_string(config_file)?;
+
+    let config: Config = serde_yaml::from_str(&config_contents)?;
+    let aliases = &config.aliases;
 
-    let commands: Vec<Command> = serde_yaml::from_str(&config)?;
+    if !config.tasks.is_empty() {
+        if let Err(error) = run_tasks(config.tasks, aliases) {
+            if cli.keep_going {
+                eprintln!("{}", error);
+            } else {
+                return Err(error.into());
+            }
+        }
+        return Ok(());
+    }
 
-    for command in commands {
+    for command in config.commands {
         match command {
             Command::Single(cmd) => {
-                println!("{}", cmd);
-                let result = run(&cmd);
-                println!(
-                    "{}",
-                    if result.wait {
-                        String::from_utf8_lossy(&result.child?.wait_with_output()?.stdout)
-                            .to_string()
+                if let Err(error) = run_and_check(&cmd, aliases) {
+                    if cli.keep_going {
+                        eprintln!("{}", error);
                     } else {
-                        format!("spawned {}", cmd)
+                        return Err(error.into());
                     }
-                );
+                }
             }
             Command::Parallel(cmds) => {
-                cmds.par_iter().for_each(|cmd| {
-                    println!("{}", cmd);
-                    let result = run(cmd);
-                    println!(
-                        "{}",
-                        if result.wait {
-                            String::from_utf8_lossy(
-                                &result.child.unwrap().wait_with_output().unwrap().stdout,
-                            )
-                            .to_string()
-                        } else {
-                            format!("spawned {}", cmd)
-                        }
-                    );
-                });
+                if let Err(error) = run_batch(&cmds, aliases) {
+                    if cli.keep_going {
+                        eprintln!("{}", error);
+                    } else {
+                        return Err(error.into());
+                    }
+                }
+            }
+            Command::ForEach { inputs, template } => {
+                let template = resolve_alias(&template, aliases)?;
+                let cmds: Vec<CommandType> = inputs
+                    .iter()
+                    .map(|input| for_each_expand(&template, input))
+                    .collect();
+
+                if let Err(error) = run_batch(&cmds, aliases) {
+                    if cli.keep_going {
+                        eprintln!("{}", error);
+                    } else {
+                        return Err(error.into());
+                    }
+                }
+            }
+            Command::Pipeline(stages) => {
+                if let Err(error) = run_pipeline(&stages, aliases) {
+                    if cli.keep_going {
+                        eprintln!("{}", error);
+                    } else {
+                        return Err(error.into());
+                    }
+                }
             }
         }
     }